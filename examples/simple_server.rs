@@ -1,4 +1,4 @@
-use gophermap::{GopherMenu,ItemType};
+use gophermap::{sanitize, GopherMenu, GopherRequest, ItemType};
 use std::io::{self, BufRead, BufReader};
 use std::net::{TcpListener, TcpStream};
 use std::thread;
@@ -9,16 +9,20 @@ const PORT: u16 = 1234;
 fn handle_client(stream: TcpStream) -> io::Result<()> {
     let mut line = String::new();
     BufReader::new(stream.try_clone()?).read_line(&mut line)?;
-    let line = line.trim();
 
-    println!("New request: {}", line);
+    let request = GopherRequest::from(&line).unwrap_or(GopherRequest {
+        selector: line.trim(),
+        query: None,
+    });
+
+    println!("New request: {}", request.selector);
 
     let mut menu = GopherMenu::with_write(&stream);
 
     let menu_link = |text: &str, selector: &str|
         menu.write_entry(ItemType::Directory, text, selector, HOST, PORT);
 
-    match line {
+    match request.selector {
         "/" | "" => {
             menu.info("Hi!")?;
             menu.info("Welcome to my Gopher server!")?;
@@ -27,6 +31,7 @@ fn handle_client(stream: TcpStream) -> io::Result<()> {
             menu_link("Potatoes", "/potato")?;
             menu.info("Opinion piece about potatoes")?;
             menu_link("Go to unknown link", "/lel")?;
+            menu.write_entry(ItemType::Search, "Search vegetables", "/search", HOST, PORT)?;
         }
         "/tomato" => {
             menu.info("Tomatoes are not good")?;
@@ -36,8 +41,16 @@ fn handle_client(stream: TcpStream) -> io::Result<()> {
             menu.info("Potatoes are the best")?;
             menu_link("Home page", "/")?;
         }
+        "/search" => match request.query {
+            Some(query) => {
+                menu.info(&format!("You searched for: {}", sanitize(query)))?;
+            }
+            None => {
+                menu.info("Usage: send a search query for this selector")?;
+            }
+        },
         x => {
-            menu.info(&format!("Unknown link: {}", x))?;
+            menu.info(&format!("Unknown link: {}", sanitize(x)))?;
             menu_link("Home page", "/")?;
         }
     };
@@ -0,0 +1,95 @@
+//! Support for the geomyidae-style `.gph` shorthand menu format, which lets
+//! server authors write a gophermap source file instead of emitting
+//! `GopherEntry` values by hand.
+//!
+//! A plain line with no TAB becomes an `ItemType::Info` entry. A line
+//! starting with a type character followed by TAB-separated fields
+//! (`<type><display>\t<selector>\t[host]\t[port]`) becomes a full entry,
+//! with a missing host or port filled in from the renderer's defaults.
+
+use crate::{GopherEntry, ItemType};
+use std::io::{self, Write};
+
+/// Renders a `.gph` source string into wire-format menu bytes, appending
+/// the `.` terminator. `default_host` and `default_port` fill in the host
+/// and port fields for entries that omit them, so authors can write
+/// relative links like `1My Dir\t/dir`.
+pub fn render_gph<W>(src: &str, default_host: &str, default_port: u16, mut out: W) -> io::Result<()>
+where
+    W: Write,
+{
+    for line in src.lines() {
+        if !line.contains('\t') {
+            GopherEntry {
+                item_type: ItemType::Info,
+                display_string: line,
+                selector: "FAKE",
+                host: "fake.host",
+                port: 1,
+            }
+            .write(&mut out)?;
+            continue;
+        }
+
+        let mut fields = line.splitn(4, '\t');
+        let mut head = fields.next().unwrap_or("").chars();
+        let item_type = ItemType::from(head.next().unwrap_or('i'));
+        let display_string = head.as_str();
+
+        let selector = fields.next().unwrap_or("");
+        let host = fields
+            .next()
+            .filter(|field| !field.is_empty())
+            .unwrap_or(default_host);
+        let port = fields
+            .next()
+            .filter(|field| !field.is_empty())
+            .and_then(|field| field.parse().ok())
+            .unwrap_or(default_port);
+
+        GopherEntry {
+            item_type,
+            display_string,
+            selector,
+            host,
+            port,
+        }
+        .write(&mut out)?;
+    }
+
+    write!(out, ".\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_gph() {
+        let src = "Welcome to my page\n1My Dir\t/dir\ni1Explicit\t/explicit\tgopher.floodgap.com\t70";
+        let mut output = Vec::new();
+        render_gph(src, "example.com", 70, &mut output).unwrap();
+        let menu = String::from_utf8(output).unwrap();
+
+        assert_eq!(
+            menu,
+            "iWelcome to my page\tFAKE\tfake.host\t1\r\n\
+             1My Dir\t/dir\texample.com\t70\r\n\
+             i1Explicit\t/explicit\tgopher.floodgap.com\t70\r\n\
+             .\r\n"
+        );
+    }
+
+    #[test]
+    fn test_render_gph_keeps_url_selector() {
+        let src = "hExample\tURL:https://example.com";
+        let mut output = Vec::new();
+        render_gph(src, "example.com", 70, &mut output).unwrap();
+        let menu = String::from_utf8(output).unwrap();
+
+        assert_eq!(
+            menu,
+            "hExample\tURL:https://example.com\texample.com\t70\r\n.\r\n"
+        );
+    }
+}
@@ -7,6 +7,8 @@
 
 use std::io::Write;
 
+pub mod gph;
+
 /// A single entry in a Gopher map. This struct can be filled in order to
 /// generate Gopher responses. It can also be the result of parsing one.
 pub struct GopherEntry<'a> {
@@ -39,6 +41,12 @@ impl<'a> GopherEntry<'a> {
             chars.as_str()
         };
 
+        Self::from_fields(line)
+    }
+
+    /// Parses a single entry line that has already had its trailing CRLF
+    /// removed. Shared by `from` and `parse_menu`.
+    fn from_fields(line: &'a str) -> Option<Self> {
         let mut parts = line.split('\t');
 
         Some(GopherEntry {
@@ -71,6 +79,123 @@ impl<'a> GopherEntry<'a> {
         )?;
         Ok(())
     }
+
+    /// Serializes a Gopher entry like [`write`](GopherEntry::write), but
+    /// first checks `item_type`, `display_string`, `selector` and `host`
+    /// for raw TAB, CR or LF bytes. A caller that echoes user-controlled
+    /// text (a selector, a search query, an `ItemType::Other` char) into
+    /// an entry should use this instead of `write`, since an unchecked
+    /// field could forge extra menu entries or truncate the response.
+    pub fn write_checked<W>(&self, mut buf: W) -> Result<(), GopherError>
+    where
+        W: Write,
+    {
+        if ['\t', '\r', '\n'].contains(&self.item_type.to_char()) {
+            return Err(GopherError::UnsafeField);
+        }
+
+        for field in [self.display_string, self.selector, self.host] {
+            if field.contains(['\t', '\r', '\n']) {
+                return Err(GopherError::UnsafeField);
+            }
+        }
+
+        self.write(&mut buf)?;
+        Ok(())
+    }
+}
+
+/// Replaces TAB, CR and LF bytes in `field` with spaces, so it is safe to
+/// use as a `GopherEntry` field even when it comes from user input.
+pub fn sanitize(field: &str) -> String {
+    field.replace(['\t', '\r', '\n'], " ")
+}
+
+/// An error produced while writing a Gopher response.
+#[derive(Debug)]
+pub enum GopherError {
+    /// A field passed to [`GopherEntry::write_checked`] contained a raw
+    /// TAB, CR or LF byte, which would corrupt the menu wire format.
+    UnsafeField,
+    /// The underlying writer returned an I/O error.
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for GopherError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GopherError::UnsafeField => {
+                write!(f, "field contains a TAB, CR or LF byte")
+            }
+            GopherError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for GopherError {}
+
+impl From<std::io::Error> for GopherError {
+    fn from(err: std::io::Error) -> Self {
+        GopherError::Io(err)
+    }
+}
+
+/// Parses a full Gopher menu response into its entries. Splits `input` on
+/// CRLF-terminated lines, stops at the lone `.` terminator line, and skips
+/// any line that fails to parse instead of aborting the whole menu. A
+/// final line missing its trailing terminator is still parsed.
+pub fn parse_menu(input: &str) -> Vec<GopherEntry<'_>> {
+    let mut entries = Vec::new();
+
+    for line in input.split("\r\n") {
+        if line == "." {
+            break;
+        }
+
+        if let Some(entry) = GopherEntry::from_fields(line) {
+            entries.push(entry);
+        }
+    }
+
+    entries
+}
+
+/// A parsed client request line, as sent by a Gopher client when it
+/// connects. Servers can use this on the line they have read instead of
+/// hand-rolling the CRLF-stripping and tab-splitting logic themselves; it
+/// does not read from the connection.
+pub struct GopherRequest<'a> {
+    /// The selector the client is requesting.
+    pub selector: &'a str,
+    /// The search query, present when the client is talking to an
+    /// Index-Search (`ItemType::Search`) selector.
+    pub query: Option<&'a str>,
+}
+
+impl<'a> GopherRequest<'a> {
+    /// Parse a client request line into a `GopherRequest`.
+    /// ```rust
+    /// use gophermap::GopherRequest;
+    /// let request = GopherRequest::from("/search\thello world\r\n").unwrap();
+    /// assert_eq!(request.selector, "/search");
+    /// assert_eq!(request.query, Some("hello world"));
+    /// ```
+    pub fn from(line: &'a str) -> Option<Self> {
+        let line = {
+            let mut chars = line.chars();
+            if !(chars.next_back()? == '\n' && chars.next_back()? == '\r') {
+                return None;
+            }
+            chars.as_str()
+        };
+
+        let mut parts = line.splitn(2, '\t');
+
+        Some(GopherRequest {
+            selector: parts.next()?,
+            query: parts.next(),
+        })
+    }
 }
 
 pub struct GopherMenu<W>
@@ -96,6 +221,30 @@ where
         self.write_entry(ItemType::Error, text, "FAKE", "fake.host", 1)
     }
 
+    /// Like [`info`](GopherMenu::info), but via [`GopherEntry::write_checked`].
+    pub fn info_checked(&self, text: &str) -> Result<(), GopherError> {
+        self.write_entry_checked(ItemType::Info, text, "FAKE", "fake.host", 1)
+    }
+
+    /// Like [`error`](GopherMenu::error), but via [`GopherEntry::write_checked`].
+    pub fn error_checked(&self, text: &str) -> Result<(), GopherError> {
+        self.write_entry_checked(ItemType::Error, text, "FAKE", "fake.host", 1)
+    }
+
+    /// Writes an HTML entry pointing to an external URL, using the
+    /// de-facto `URL:` selector convention that gateway-aware clients
+    /// understand. Goes through [`GopherEntry::write_checked`], since
+    /// `text`/`url` commonly come from user-submitted content.
+    pub fn write_url(&self, text: &str, url: &str) -> Result<(), GopherError> {
+        self.write_entry_checked(
+            ItemType::Html,
+            text,
+            &format!("URL:{}", url),
+            "fake.host",
+            1,
+        )
+    }
+
     pub fn write_entry(
         &self,
         item_type: ItemType,
@@ -114,6 +263,26 @@ where
         .write(self.target)
     }
 
+    /// Like [`write_entry`](GopherMenu::write_entry), but via
+    /// [`GopherEntry::write_checked`].
+    pub fn write_entry_checked(
+        &self,
+        item_type: ItemType,
+        text: &str,
+        selector: &str,
+        host: &str,
+        port: u16,
+    ) -> Result<(), GopherError> {
+        GopherEntry {
+            item_type,
+            display_string: text,
+            selector,
+            host,
+            port,
+        }
+        .write_checked(self.target)
+    }
+
     pub fn end(&mut self) -> std::io::Result<()> {
         write!(self.target, ".\r\n")
     }
@@ -154,6 +323,14 @@ pub enum ItemType {
     Image,
     /// Informational message
     Info,
+    /// Item is an HTML file.
+    Html,
+    /// Item is a PNG format graphics file.
+    Png,
+    /// Item is a sound file.
+    Sound,
+    /// Item is a document (e.g. a PDF or word processor file).
+    Document,
     /// Other types
     Other(char),
 }
@@ -177,6 +354,10 @@ impl ItemType {
             'g' => ItemType::Gif,
             'I' => ItemType::Image,
             'i' => ItemType::Info,
+            'h' => ItemType::Html,
+            'p' => ItemType::Png,
+            's' => ItemType::Sound,
+            'd' => ItemType::Document,
             c => ItemType::Other(c),
         }
     }
@@ -199,14 +380,55 @@ impl ItemType {
             ItemType::Gif => 'g',
             ItemType::Image => 'I',
             ItemType::Info => 'i',
+            ItemType::Html => 'h',
+            ItemType::Png => 'p',
+            ItemType::Sound => 's',
+            ItemType::Document => 'd',
             ItemType::Other(c) => *c,
         }
     }
+
+    /// Returns true for item types that a client must download and save
+    /// to disk rather than render inline.
+    pub fn is_download(&self) -> bool {
+        matches!(
+            self,
+            ItemType::BinHex
+                | ItemType::DosBinary
+                | ItemType::Uuencoded
+                | ItemType::Binary
+                | ItemType::Gif
+                | ItemType::Image
+                | ItemType::Png
+                | ItemType::Sound
+                | ItemType::Document
+        )
+    }
+
+    /// Returns true for item types whose content is plain, renderable text.
+    pub fn is_text(&self) -> bool {
+        matches!(self, ItemType::File | ItemType::Info)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::RefCell;
+
+    /// A `Write` sink that implements `Write` for `&Self`, like
+    /// `TcpStream`, so it can back a `GopherMenu` in tests.
+    struct SharedBuf(RefCell<Vec<u8>>);
+
+    impl Write for &SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
 
     fn get_test_pairs() -> Vec<(String, GopherEntry<'static>)> {
         let mut pairs = Vec::new();
@@ -257,4 +479,159 @@ mod tests {
             assert_eq!(raw, line);
         }
     }
+
+    #[test]
+    fn test_request_with_query() {
+        let request = GopherRequest::from("/search\thello world\r\n").unwrap();
+        assert_eq!(request.selector, "/search");
+        assert_eq!(request.query, Some("hello world"));
+    }
+
+    #[test]
+    fn test_request_without_query() {
+        let request = GopherRequest::from("/home\r\n").unwrap();
+        assert_eq!(request.selector, "/home");
+        assert_eq!(request.query, None);
+    }
+
+    #[test]
+    fn test_request_empty_selector() {
+        let request = GopherRequest::from("\r\n").unwrap();
+        assert_eq!(request.selector, "");
+        assert_eq!(request.query, None);
+    }
+
+    #[test]
+    fn test_request_missing_crlf() {
+        assert!(GopherRequest::from("/home").is_none());
+    }
+
+    #[test]
+    fn test_item_type_roundtrip() {
+        for c in ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '+', 'T', 'g', 'I', 'i', 'h', 'p', 's', 'd'] {
+            assert_eq!(ItemType::from(c).to_char(), c);
+        }
+    }
+
+    #[test]
+    fn test_is_download() {
+        assert!(ItemType::Binary.is_download());
+        assert!(ItemType::Png.is_download());
+        assert!(ItemType::Sound.is_download());
+        assert!(ItemType::Document.is_download());
+        assert!(!ItemType::Directory.is_download());
+        assert!(!ItemType::File.is_download());
+    }
+
+    #[test]
+    fn test_is_text() {
+        assert!(ItemType::File.is_text());
+        assert!(ItemType::Info.is_text());
+        assert!(!ItemType::Directory.is_text());
+    }
+
+    #[test]
+    fn test_parse_menu() {
+        let menu = "iWelcome to my page\tFAKE\t(NULL)\t0\r\n\
+                    1Floodgap Home\t/home\tgopher.floodgap.com\t70\r\n\
+                    .\r\n";
+        let entries = parse_menu(menu);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].item_type, ItemType::Info);
+        assert_eq!(entries[1].selector, "/home");
+    }
+
+    #[test]
+    fn test_parse_menu_missing_terminator() {
+        let menu = "1Floodgap Home\t/home\tgopher.floodgap.com\t70\r\n";
+        let entries = parse_menu(menu);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].selector, "/home");
+    }
+
+    #[test]
+    fn test_parse_menu_skips_unparsable_lines() {
+        let menu = "this is not a valid entry\r\n1Home\t/\tgopher.floodgap.com\t70\r\n.\r\n";
+        let entries = parse_menu(menu);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].selector, "/");
+    }
+
+    #[test]
+    fn test_write_checked_rejects_tab_in_field() {
+        let entry = GopherEntry {
+            item_type: ItemType::Info,
+            display_string: "Unknown link: /lel\tsneaky",
+            selector: "FAKE",
+            host: "fake.host",
+            port: 1,
+        };
+        let mut output = Vec::new();
+        assert!(matches!(
+            entry.write_checked(&mut output),
+            Err(GopherError::UnsafeField)
+        ));
+    }
+
+    #[test]
+    fn test_write_checked_rejects_unsafe_item_type() {
+        let entry = GopherEntry {
+            item_type: ItemType::Other('\n'),
+            display_string: "Unknown link: /lel",
+            selector: "FAKE",
+            host: "fake.host",
+            port: 1,
+        };
+        let mut output = Vec::new();
+        assert!(matches!(
+            entry.write_checked(&mut output),
+            Err(GopherError::UnsafeField)
+        ));
+    }
+
+    #[test]
+    fn test_write_checked_allows_clean_fields() {
+        let entry = GopherEntry {
+            item_type: ItemType::Info,
+            display_string: "Unknown link: /lel",
+            selector: "FAKE",
+            host: "fake.host",
+            port: 1,
+        };
+        let mut output = Vec::new();
+        entry.write_checked(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "iUnknown link: /lel\tFAKE\tfake.host\t1\r\n"
+        );
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize("a\tb\rc\nd"), "a b c d");
+        assert_eq!(sanitize("clean"), "clean");
+    }
+
+    #[test]
+    fn test_write_url() {
+        let buf = SharedBuf(RefCell::new(Vec::new()));
+        let menu = GopherMenu::with_write(&buf);
+        menu.write_url("My Site", "https://example.com").unwrap();
+
+        assert_eq!(
+            String::from_utf8(buf.0.into_inner()).unwrap(),
+            "hMy Site\tURL:https://example.com\tfake.host\t1\r\n"
+        );
+    }
+
+    #[test]
+    fn test_write_url_rejects_unsafe_url() {
+        let buf = SharedBuf(RefCell::new(Vec::new()));
+        let menu = GopherMenu::with_write(&buf);
+
+        assert!(matches!(
+            menu.write_url("My Site", "https://example.com\r\n3Injected\t/\tfake.host\t1"),
+            Err(GopherError::UnsafeField)
+        ));
+    }
 }